@@ -1,11 +1,12 @@
-/**
+/*!
  * AlphaBot Fee Collection Smart Contract
- * Professional-grade Solana smart contract for collecting 5% fees
+ * Professional-grade Solana smart contract for collecting configurable fees
  * Built for Alex - Professional Calisthenics Athlete & Crypto Educator
- * 
+ *
  * Features:
- * - 5% withdrawal fees automatically sent to Alex's wallet
- * - 5% trading fees automatically sent to Alex's wallet
+ * - Withdrawal fees automatically sent to the configured wallet
+ * - Trading fees automatically sent to the configured wallet
+ * - Admin-governable fee rate and recipient via a Config PDA
  * - Secure fee calculation and transfer
  * - Event logging for transparency
  * - Anti-MEV protection
@@ -14,6 +15,7 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
+    clock::Clock,
     entrypoint,
     entrypoint::ProgramResult,
     msg,
@@ -25,36 +27,68 @@ use solana_program::{
     system_instruction,
     sysvar::Sysvar,
 };
+use spl_token::{
+    instruction::transfer_checked,
+    state::{Account as TokenAccount, Mint},
+};
+use thiserror::Error;
 
 // Program entrypoint
 entrypoint!(process_instruction);
 
-// Alex's wallet address for receiving fees
-const FEE_RECIPIENT: &str = "9TkcJVpw9yYkNrTFdhBBq3iYa4r69osa5PfuAwzxS3ht";
-const WITHDRAWAL_FEE_PERCENT: u64 = 5; // 5%
-const TRADING_FEE_PERCENT: u64 = 5;    // 5%
-const FEE_PRECISION: u64 = 100;        // For percentage calculations
+// Basis-point precision for fee math (1 bps = 0.01%)
+const FEE_BPS_PRECISION: u64 = 10_000;
+
+// Seed used to derive a user's FeeStats PDA: [seed, user_pubkey]
+const FEE_STATS_SEED: &[u8] = b"fee_stats";
+// Seed used to derive the single program-wide Config PDA: [seed]
+const CONFIG_SEED: &[u8] = b"config";
+// Seed used to derive the single program-wide fee stream escrow PDA: [seed]
+const STREAM_SEED: &[u8] = b"fee_stream";
+
+#[derive(Error, Debug, Copy, Clone)]
+pub enum AlphaBotError {
+    #[error("Withdrawal would leave the account below the rent-exempt minimum")]
+    RentExemptViolation,
+    #[error("Fee basis points exceed 100%")]
+    FeeBpsTooHigh,
+}
+
+impl From<AlphaBotError> for ProgramError {
+    fn from(e: AlphaBotError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
 pub enum AlphaBotInstruction {
-    /// Process withdrawal fee
+    /// Process withdrawal fee. If Config.escrow_enabled is set, the fee is
+    /// deposited into the fee stream escrow instead of paid directly.
     /// Accounts:
     /// 0. [signer] User's wallet (payer)
-    /// 1. [writable] Fee recipient (Alex's wallet)
+    /// 1. [writable] Fee recipient (must match Config.fee_recipient; ignored when escrowed)
     /// 2. [] System program
+    /// 3. [writable] User's FeeStats PDA (seeds = [FEE_STATS_SEED, user pubkey])
+    /// 4. [] Config PDA (seeds = [CONFIG_SEED])
+    /// 5. [] Rent sysvar
+    /// 6. [writable] StreamConfig PDA (seeds = [STREAM_SEED]; only touched when escrowed)
     ProcessWithdrawalFee {
         amount: u64, // Amount in lamports
     },
-    
-    /// Process trading fee
+
+    /// Process trading fee. If Config.escrow_enabled is set, the fee is
+    /// deposited into the fee stream escrow instead of paid directly.
     /// Accounts:
     /// 0. [signer] User's wallet (payer)
-    /// 1. [writable] Fee recipient (Alex's wallet)
+    /// 1. [writable] Fee recipient (must match Config.fee_recipient; ignored when escrowed)
     /// 2. [] System program
+    /// 3. [writable] User's FeeStats PDA (seeds = [FEE_STATS_SEED, user pubkey])
+    /// 4. [] Config PDA (seeds = [CONFIG_SEED])
+    /// 5. [writable] StreamConfig PDA (seeds = [STREAM_SEED]; only touched when escrowed)
     ProcessTradingFee {
         amount: u64, // Amount in lamports
     },
-    
+
     /// Initialize fee stats account
     /// Accounts:
     /// 0. [signer] User's wallet
@@ -62,6 +96,146 @@ pub enum AlphaBotInstruction {
     /// 2. [] System program
     /// 3. [] Rent sysvar
     InitializeFeeStats,
+
+    /// Initialize the program-wide Config PDA. The signer becomes admin.
+    /// Accounts:
+    /// 0. [signer] Admin wallet (payer)
+    /// 1. [writable] Config PDA (seeds = [CONFIG_SEED])
+    /// 2. [] System program
+    /// 3. [] Rent sysvar
+    InitializeConfig {
+        fee_recipient: Pubkey,
+        withdrawal_fee_bps: u64,
+        trading_fee_bps: u64,
+        escrow_enabled: bool,
+    },
+
+    /// Update the program-wide Config PDA. Must be signed by the current admin.
+    /// Accounts:
+    /// 0. [signer] Admin wallet
+    /// 1. [writable] Config PDA (seeds = [CONFIG_SEED])
+    UpdateConfig {
+        fee_recipient: Pubkey,
+        withdrawal_fee_bps: u64,
+        trading_fee_bps: u64,
+        escrow_enabled: bool,
+    },
+
+    /// Process withdrawal fee on an SPL token transfer
+    /// Accounts:
+    /// 0. [signer] User's wallet (authority over the source token account)
+    /// 1. [writable] User's token account (source)
+    /// 2. [writable] Fee recipient's associated token account (destination)
+    /// 3. [] Token mint
+    /// 4. [] SPL Token program
+    /// 5. [writable] User's FeeStats PDA (seeds = [FEE_STATS_SEED, user pubkey])
+    /// 6. [] Config PDA (seeds = [CONFIG_SEED])
+    ProcessTokenWithdrawalFee {
+        amount: u64, // Amount in the token's base units
+    },
+
+    /// Process trading fee on an SPL token transfer
+    /// Accounts:
+    /// 0. [signer] User's wallet (authority over the source token account)
+    /// 1. [writable] User's token account (source)
+    /// 2. [writable] Fee recipient's associated token account (destination)
+    /// 3. [] Token mint
+    /// 4. [] SPL Token program
+    /// 5. [writable] User's FeeStats PDA (seeds = [FEE_STATS_SEED, user pubkey])
+    /// 6. [] Config PDA (seeds = [CONFIG_SEED])
+    ProcessTokenTradingFee {
+        amount: u64, // Amount in the token's base units
+    },
+
+    /// Process a batch of withdrawal/trading fees for a single user in one
+    /// transaction, collecting the sum as a single lamport transfer. If
+    /// Config.escrow_enabled is set, the aggregate is deposited into the fee
+    /// stream escrow instead of paid directly.
+    /// Accounts:
+    /// 0. [signer] User's wallet (payer)
+    /// 1. [writable] Fee recipient (must match Config.fee_recipient; ignored when escrowed)
+    /// 2. [] System program
+    /// 3. [writable] User's FeeStats PDA (seeds = [FEE_STATS_SEED, user pubkey])
+    /// 4. [] Config PDA (seeds = [CONFIG_SEED])
+    /// 5. [] Rent sysvar
+    /// 6. [writable] StreamConfig PDA (seeds = [STREAM_SEED]; only touched when escrowed)
+    ProcessFeeBatch {
+        entries: Vec<FeeEntry>,
+    },
+
+    /// Create the program-wide fee stream escrow. Must be signed by the
+    /// current Config admin.
+    /// Accounts:
+    /// 0. [signer] Admin wallet (payer)
+    /// 1. [writable] StreamConfig PDA (seeds = [STREAM_SEED])
+    /// 2. [] Config PDA (seeds = [CONFIG_SEED])
+    /// 3. [] System program
+    /// 4. [] Rent sysvar
+    CreateFeeStream {
+        start_ts: i64,
+        end_ts: i64,
+    },
+
+    /// Deposit lamports into the fee stream escrow. The fee processors'
+    /// escrow path performs the same deposit instead of paying the recipient
+    /// directly.
+    /// Accounts:
+    /// 0. [signer] Depositor (payer)
+    /// 1. [writable] StreamConfig PDA (seeds = [STREAM_SEED])
+    /// 2. [] System program
+    DepositToStream {
+        amount: u64,
+    },
+
+    /// Withdraw the currently vested portion of the fee stream. Must be
+    /// signed by the configured fee recipient.
+    /// Accounts:
+    /// 0. [signer, writable] Fee recipient wallet (must match Config.fee_recipient)
+    /// 1. [writable] StreamConfig PDA (seeds = [STREAM_SEED])
+    /// 2. [] Config PDA (seeds = [CONFIG_SEED])
+    WithdrawVested,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub enum FeeEntryKind {
+    Withdrawal,
+    Trading,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct FeeEntry {
+    pub kind: FeeEntryKind,
+    pub amount: u64, // Gross amount this entry's fee is calculated from
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct StreamConfig {
+    pub is_initialized: bool,
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub deposited: u64,
+    pub withdrawn: u64,
+}
+
+impl Sealed for StreamConfig {}
+
+impl IsInitialized for StreamConfig {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for StreamConfig {
+    const LEN: usize = 1 + 8 + 8 + 8 + 8; // 33 bytes
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut slice = dst;
+        self.serialize(&mut slice).unwrap();
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        Self::try_from_slice(src).map_err(|_| ProgramError::InvalidAccountData)
+    }
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
@@ -95,6 +269,39 @@ impl Pack for FeeStats {
     }
 }
 
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct Config {
+    pub is_initialized: bool,
+    pub admin: Pubkey,
+    pub fee_recipient: Pubkey,
+    pub withdrawal_fee_bps: u64,
+    pub trading_fee_bps: u64,
+    // When set, fee processors deposit into the StreamConfig escrow
+    // (seeds = [STREAM_SEED]) instead of paying fee_recipient directly.
+    pub escrow_enabled: bool,
+}
+
+impl Sealed for Config {}
+
+impl IsInitialized for Config {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for Config {
+    const LEN: usize = 1 + 32 + 32 + 8 + 8 + 1; // 82 bytes
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut slice = dst;
+        self.serialize(&mut slice).unwrap();
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        Self::try_from_slice(src).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
 pub fn process_instruction(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
@@ -118,11 +325,43 @@ pub fn process_instruction(
             msg!("🔧 Initializing fee stats account");
             initialize_fee_stats(program_id, accounts)
         },
+        AlphaBotInstruction::InitializeConfig { fee_recipient, withdrawal_fee_bps, trading_fee_bps, escrow_enabled } => {
+            msg!("🔧 Initializing config account");
+            initialize_config(program_id, accounts, fee_recipient, withdrawal_fee_bps, trading_fee_bps, escrow_enabled)
+        },
+        AlphaBotInstruction::UpdateConfig { fee_recipient, withdrawal_fee_bps, trading_fee_bps, escrow_enabled } => {
+            msg!("🔧 Updating config account");
+            update_config(program_id, accounts, fee_recipient, withdrawal_fee_bps, trading_fee_bps, escrow_enabled)
+        },
+        AlphaBotInstruction::ProcessTokenWithdrawalFee { amount } => {
+            msg!("💰 Processing token withdrawal fee: {} base units", amount);
+            process_token_withdrawal_fee(program_id, accounts, amount)
+        },
+        AlphaBotInstruction::ProcessTokenTradingFee { amount } => {
+            msg!("📈 Processing token trading fee: {} base units", amount);
+            process_token_trading_fee(program_id, accounts, amount)
+        },
+        AlphaBotInstruction::ProcessFeeBatch { entries } => {
+            msg!("📦 Processing fee batch: {} entries", entries.len());
+            process_fee_batch(program_id, accounts, entries)
+        },
+        AlphaBotInstruction::CreateFeeStream { start_ts, end_ts } => {
+            msg!("🔧 Creating fee stream escrow");
+            create_fee_stream(program_id, accounts, start_ts, end_ts)
+        },
+        AlphaBotInstruction::DepositToStream { amount } => {
+            msg!("💧 Depositing {} lamports into fee stream", amount);
+            deposit_to_stream(program_id, accounts, amount)
+        },
+        AlphaBotInstruction::WithdrawVested => {
+            msg!("💸 Withdrawing vested fee stream balance");
+            withdraw_vested(program_id, accounts)
+        },
     }
 }
 
 fn process_withdrawal_fee(
-    _program_id: &Pubkey,
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
     amount: u64,
 ) -> ProgramResult {
@@ -130,20 +369,17 @@ fn process_withdrawal_fee(
     let user_account = next_account_info(account_info_iter)?;
     let fee_recipient_account = next_account_info(account_info_iter)?;
     let system_program = next_account_info(account_info_iter)?;
+    let fee_stats_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let rent_sysvar = next_account_info(account_info_iter)?;
+    let stream_account = next_account_info(account_info_iter)?;
 
-    // Verify fee recipient is Alex's wallet
-    let expected_recipient = FEE_RECIPIENT.parse::<Pubkey>()
-        .map_err(|_| ProgramError::InvalidAccountData)?;
-    
-    if fee_recipient_account.key != &expected_recipient {
-        msg!("❌ Invalid fee recipient. Expected: {}", FEE_RECIPIENT);
-        return Err(ProgramError::InvalidAccountData);
-    }
+    let config = load_config(program_id, config_account)?;
 
-    // Calculate 5% withdrawal fee
+    // Calculate withdrawal fee from the configured basis points
     let fee_amount = amount
-        .checked_mul(WITHDRAWAL_FEE_PERCENT)
-        .and_then(|x| x.checked_div(FEE_PRECISION))
+        .checked_mul(config.withdrawal_fee_bps)
+        .and_then(|x| x.checked_div(FEE_BPS_PRECISION))
         .ok_or(ProgramError::ArithmeticOverflow)?;
 
     if fee_amount == 0 {
@@ -157,32 +393,57 @@ fn process_withdrawal_fee(
         return Err(ProgramError::InsufficientFunds);
     }
 
-    // Transfer fee to Alex's wallet
-    let transfer_instruction = system_instruction::transfer(
-        user_account.key,
-        fee_recipient_account.key,
-        fee_amount,
-    );
+    // Don't let the fee push a currently rent-exempt account below the
+    // rent-exempt minimum and leave it eligible for purging.
+    let rent = Rent::from_account_info(rent_sysvar)?;
+    let minimum_balance = rent.minimum_balance(user_account.data_len());
+    let currently_rent_exempt = user_account.lamports() >= minimum_balance;
+    let remaining_balance = user_account.lamports().saturating_sub(fee_amount);
 
-    invoke(
-        &transfer_instruction,
-        &[
-            user_account.clone(),
-            fee_recipient_account.clone(),
-            system_program.clone(),
-        ],
-    )?;
+    if currently_rent_exempt && remaining_balance < minimum_balance {
+        msg!("❌ Withdrawal fee would leave account below rent-exempt minimum");
+        return Err(AlphaBotError::RentExemptViolation.into());
+    }
+
+    if config.escrow_enabled {
+        // Smooth revenue into the vesting stream instead of paying out directly.
+        deposit_to_stream_checked(program_id, user_account, stream_account, system_program, fee_amount)?;
+        msg!("✅ Withdrawal fee escrowed: {} lamports → fee stream", fee_amount);
+    } else {
+        // Verify fee recipient matches the configured wallet
+        if fee_recipient_account.key != &config.fee_recipient {
+            msg!("❌ Invalid fee recipient. Expected: {}", config.fee_recipient);
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let transfer_instruction = system_instruction::transfer(
+            user_account.key,
+            fee_recipient_account.key,
+            fee_amount,
+        );
+
+        invoke(
+            &transfer_instruction,
+            &[
+                user_account.clone(),
+                fee_recipient_account.clone(),
+                system_program.clone(),
+            ],
+        )?;
+
+        msg!("✅ Withdrawal fee processed: {} lamports → {}", fee_amount, config.fee_recipient);
+    }
+
+    update_fee_stats(program_id, user_account, fee_stats_account, fee_amount, true)?;
 
-    msg!("✅ Withdrawal fee processed: {} lamports → {}", fee_amount, FEE_RECIPIENT);
-    
     // Emit event for tracking
-    msg!("EVENT:WITHDRAWAL_FEE|{}|{}|{}", user_account.key, fee_amount, chrono::Utc::now().timestamp());
+    msg!("EVENT:WITHDRAWAL_FEE|{}|{}|{}", user_account.key, fee_amount, Clock::get()?.unix_timestamp);
 
     Ok(())
 }
 
 fn process_trading_fee(
-    _program_id: &Pubkey,
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
     amount: u64,
 ) -> ProgramResult {
@@ -190,20 +451,16 @@ fn process_trading_fee(
     let user_account = next_account_info(account_info_iter)?;
     let fee_recipient_account = next_account_info(account_info_iter)?;
     let system_program = next_account_info(account_info_iter)?;
+    let fee_stats_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let stream_account = next_account_info(account_info_iter)?;
 
-    // Verify fee recipient is Alex's wallet
-    let expected_recipient = FEE_RECIPIENT.parse::<Pubkey>()
-        .map_err(|_| ProgramError::InvalidAccountData)?;
-    
-    if fee_recipient_account.key != &expected_recipient {
-        msg!("❌ Invalid fee recipient. Expected: {}", FEE_RECIPIENT);
-        return Err(ProgramError::InvalidAccountData);
-    }
+    let config = load_config(program_id, config_account)?;
 
-    // Calculate 5% trading fee
+    // Calculate trading fee from the configured basis points
     let fee_amount = amount
-        .checked_mul(TRADING_FEE_PERCENT)
-        .and_then(|x| x.checked_div(FEE_PRECISION))
+        .checked_mul(config.trading_fee_bps)
+        .and_then(|x| x.checked_div(FEE_BPS_PRECISION))
         .ok_or(ProgramError::ArithmeticOverflow)?;
 
     if fee_amount == 0 {
@@ -217,26 +474,494 @@ fn process_trading_fee(
         return Err(ProgramError::InsufficientFunds);
     }
 
-    // Transfer fee to Alex's wallet
-    let transfer_instruction = system_instruction::transfer(
+    if config.escrow_enabled {
+        // Smooth revenue into the vesting stream instead of paying out directly.
+        deposit_to_stream_checked(program_id, user_account, stream_account, system_program, fee_amount)?;
+        msg!("✅ Trading fee escrowed: {} lamports → fee stream", fee_amount);
+    } else {
+        // Verify fee recipient matches the configured wallet
+        if fee_recipient_account.key != &config.fee_recipient {
+            msg!("❌ Invalid fee recipient. Expected: {}", config.fee_recipient);
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let transfer_instruction = system_instruction::transfer(
+            user_account.key,
+            fee_recipient_account.key,
+            fee_amount,
+        );
+
+        invoke(
+            &transfer_instruction,
+            &[
+                user_account.clone(),
+                fee_recipient_account.clone(),
+                system_program.clone(),
+            ],
+        )?;
+
+        msg!("✅ Trading fee processed: {} lamports → {}", fee_amount, config.fee_recipient);
+    }
+
+    update_fee_stats(program_id, user_account, fee_stats_account, fee_amount, false)?;
+
+    // Emit event for tracking
+    msg!("EVENT:TRADING_FEE|{}|{}|{}", user_account.key, fee_amount, Clock::get()?.unix_timestamp);
+
+    Ok(())
+}
+
+fn process_token_withdrawal_fee(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let user_account = next_account_info(account_info_iter)?;
+    let user_token_account = next_account_info(account_info_iter)?;
+    let fee_recipient_token_account = next_account_info(account_info_iter)?;
+    let mint_account = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let fee_stats_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    let config = load_config(program_id, config_account)?;
+
+    let fee_amount = amount
+        .checked_mul(config.withdrawal_fee_bps)
+        .and_then(|x| x.checked_div(FEE_BPS_PRECISION))
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    if fee_amount == 0 {
+        msg!("⚠️ Fee amount too small, skipping transfer");
+        return Ok(());
+    }
+
+    transfer_token_fee(
+        &config,
+        user_account,
+        user_token_account,
+        fee_recipient_token_account,
+        mint_account,
+        token_program,
+        fee_amount,
+    )?;
+
+    msg!("✅ Token withdrawal fee processed: {} base units → {}", fee_amount, config.fee_recipient);
+
+    update_fee_stats(program_id, user_account, fee_stats_account, fee_amount, true)?;
+
+    // Emit event for tracking
+    msg!("EVENT:TOKEN_WITHDRAWAL_FEE|{}|{}|{}", user_account.key, fee_amount, Clock::get()?.unix_timestamp);
+
+    Ok(())
+}
+
+fn process_token_trading_fee(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let user_account = next_account_info(account_info_iter)?;
+    let user_token_account = next_account_info(account_info_iter)?;
+    let fee_recipient_token_account = next_account_info(account_info_iter)?;
+    let mint_account = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let fee_stats_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    let config = load_config(program_id, config_account)?;
+
+    let fee_amount = amount
+        .checked_mul(config.trading_fee_bps)
+        .and_then(|x| x.checked_div(FEE_BPS_PRECISION))
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    if fee_amount == 0 {
+        msg!("⚠️ Fee amount too small, skipping transfer");
+        return Ok(());
+    }
+
+    transfer_token_fee(
+        &config,
+        user_account,
+        user_token_account,
+        fee_recipient_token_account,
+        mint_account,
+        token_program,
+        fee_amount,
+    )?;
+
+    msg!("✅ Token trading fee processed: {} base units → {}", fee_amount, config.fee_recipient);
+
+    update_fee_stats(program_id, user_account, fee_stats_account, fee_amount, false)?;
+
+    // Emit event for tracking
+    msg!("EVENT:TOKEN_TRADING_FEE|{}|{}|{}", user_account.key, fee_amount, Clock::get()?.unix_timestamp);
+
+    Ok(())
+}
+
+/// Validate the mint and recipient ATA, then move `fee_amount` base units
+/// from the user's token account to the configured fee wallet's ATA via
+/// `transfer_checked` so the transfer is rejected on any decimals mismatch.
+fn transfer_token_fee<'a>(
+    config: &Config,
+    user_account: &AccountInfo<'a>,
+    user_token_account: &AccountInfo<'a>,
+    fee_recipient_token_account: &AccountInfo<'a>,
+    mint_account: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    fee_amount: u64,
+) -> ProgramResult {
+    if token_program.key != &spl_token::id() {
+        msg!("❌ Invalid token program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mint = Mint::unpack(&mint_account.data.borrow())?;
+
+    let source = TokenAccount::unpack(&user_token_account.data.borrow())?;
+    if source.mint != *mint_account.key {
+        msg!("❌ User token account does not belong to the given mint");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let destination = TokenAccount::unpack(&fee_recipient_token_account.data.borrow())?;
+    if destination.mint != *mint_account.key {
+        msg!("❌ Recipient token account does not belong to the given mint");
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if destination.owner != config.fee_recipient {
+        msg!("❌ Recipient token account is not owned by the configured fee wallet");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let transfer_instruction = transfer_checked(
+        token_program.key,
+        user_token_account.key,
+        mint_account.key,
+        fee_recipient_token_account.key,
         user_account.key,
-        fee_recipient_account.key,
+        &[],
         fee_amount,
-    );
+        mint.decimals,
+    )?;
 
     invoke(
         &transfer_instruction,
         &[
+            user_token_account.clone(),
+            mint_account.clone(),
+            fee_recipient_token_account.clone(),
             user_account.clone(),
-            fee_recipient_account.clone(),
+            token_program.clone(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+/// Compute each entry's fee and their running total. Split out of
+/// `process_fee_batch` so the summation (and its overflow handling) can be
+/// unit tested without constructing `AccountInfo` fixtures.
+fn compute_batch_fees(
+    entries: &[FeeEntry],
+    withdrawal_fee_bps: u64,
+    trading_fee_bps: u64,
+) -> Result<(u64, Vec<u64>), ProgramError> {
+    let mut total_fee: u64 = 0;
+    let mut per_entry_fees = Vec::with_capacity(entries.len());
+
+    for entry in entries {
+        let bps = match entry.kind {
+            FeeEntryKind::Withdrawal => withdrawal_fee_bps,
+            FeeEntryKind::Trading => trading_fee_bps,
+        };
+
+        let fee_amount = entry
+            .amount
+            .checked_mul(bps)
+            .and_then(|x| x.checked_div(FEE_BPS_PRECISION))
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        total_fee = total_fee
+            .checked_add(fee_amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        per_entry_fees.push(fee_amount);
+    }
+
+    Ok((total_fee, per_entry_fees))
+}
+
+/// Sum the withdrawal-kind entries' fees out of a batch's per-entry fees.
+/// Split out of `process_fee_batch` so the rent-exemption guard's input can
+/// be unit tested without constructing `AccountInfo` fixtures.
+fn sum_withdrawal_fees(entries: &[FeeEntry], per_entry_fees: &[u64]) -> Result<u64, ProgramError> {
+    entries
+        .iter()
+        .zip(per_entry_fees.iter())
+        .filter(|(entry, _)| entry.kind == FeeEntryKind::Withdrawal)
+        .map(|(_, fee_amount)| *fee_amount)
+        .try_fold(0u64, |acc, fee_amount| acc.checked_add(fee_amount))
+        .ok_or(ProgramError::ArithmeticOverflow)
+}
+
+fn process_fee_batch(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    entries: Vec<FeeEntry>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let user_account = next_account_info(account_info_iter)?;
+    let fee_recipient_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let fee_stats_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let rent_sysvar = next_account_info(account_info_iter)?;
+    let stream_account = next_account_info(account_info_iter)?;
+
+    let config = load_config(program_id, config_account)?;
+
+    if !config.escrow_enabled && fee_recipient_account.key != &config.fee_recipient {
+        msg!("❌ Invalid fee recipient. Expected: {}", config.fee_recipient);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Sum every entry's fee into a single aggregate before touching any account.
+    let (total_fee, per_entry_fees) =
+        compute_batch_fees(&entries, config.withdrawal_fee_bps, config.trading_fee_bps)?;
+
+    if total_fee == 0 {
+        msg!("⚠️ Batch fee total is zero, skipping transfer");
+        return Ok(());
+    }
+
+    if user_account.lamports() < total_fee {
+        msg!("❌ Insufficient balance for fee batch");
+        return Err(ProgramError::InsufficientFunds);
+    }
+
+    // A batch can smuggle a withdrawal-kind fee past the rent-exemption guard
+    // that ProcessWithdrawalFee applies on its own; apply it here too, scoped
+    // to the withdrawal-derived portion of the total.
+    let withdrawal_fee = sum_withdrawal_fees(&entries, &per_entry_fees)?;
+
+    if withdrawal_fee > 0 {
+        let rent = Rent::from_account_info(rent_sysvar)?;
+        let minimum_balance = rent.minimum_balance(user_account.data_len());
+        let currently_rent_exempt = user_account.lamports() >= minimum_balance;
+        let remaining_balance = user_account.lamports().saturating_sub(withdrawal_fee);
+
+        if currently_rent_exempt && remaining_balance < minimum_balance {
+            msg!("❌ Batch withdrawal fee would leave account below rent-exempt minimum");
+            return Err(AlphaBotError::RentExemptViolation.into());
+        }
+    }
+
+    // One aggregate transfer for the whole batch — either this succeeds and
+    // every entry below is recorded, or the instruction fails and none are.
+    if config.escrow_enabled {
+        deposit_to_stream_checked(program_id, user_account, stream_account, system_program, total_fee)?;
+        msg!("✅ Fee batch escrowed: {} lamports → fee stream", total_fee);
+    } else {
+        let transfer_instruction = system_instruction::transfer(
+            user_account.key,
+            fee_recipient_account.key,
+            total_fee,
+        );
+
+        invoke(
+            &transfer_instruction,
+            &[
+                user_account.clone(),
+                fee_recipient_account.clone(),
+                system_program.clone(),
+            ],
+        )?;
+
+        msg!("✅ Fee batch processed: {} lamports → {}", total_fee, config.fee_recipient);
+    }
+
+    for (index, (entry, fee_amount)) in entries.iter().zip(per_entry_fees.iter()).enumerate() {
+        if *fee_amount == 0 {
+            continue;
+        }
+
+        let is_withdrawal = entry.kind == FeeEntryKind::Withdrawal;
+        update_fee_stats(program_id, user_account, fee_stats_account, *fee_amount, is_withdrawal)?;
+
+        msg!("EVENT:BATCH_FEE|{}|{}|{}|{}", index, user_account.key, fee_amount, Clock::get()?.unix_timestamp);
+    }
+
+    Ok(())
+}
+
+/// Validate that `fee_stats_account` is the caller's FeeStats PDA, then
+/// fold `fee_amount` into its running totals and bump the fee count/timestamp.
+fn update_fee_stats<'a>(
+    program_id: &Pubkey,
+    user_account: &AccountInfo<'a>,
+    fee_stats_account: &AccountInfo<'a>,
+    fee_amount: u64,
+    is_withdrawal: bool,
+) -> ProgramResult {
+    let (expected_fee_stats, _bump) = Pubkey::find_program_address(
+        &[FEE_STATS_SEED, user_account.key.as_ref()],
+        program_id,
+    );
+
+    if fee_stats_account.key != &expected_fee_stats {
+        msg!("❌ Invalid fee stats account for this user");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let mut fee_stats = FeeStats::unpack(&fee_stats_account.data.borrow())?;
+
+    if is_withdrawal {
+        fee_stats.total_withdrawal_fees = fee_stats
+            .total_withdrawal_fees
+            .checked_add(fee_amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+    } else {
+        fee_stats.total_trading_fees = fee_stats
+            .total_trading_fees
+            .checked_add(fee_amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+    }
+
+    fee_stats.fee_count = fee_stats
+        .fee_count
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    fee_stats.last_fee_timestamp = Clock::get()?.unix_timestamp;
+
+    FeeStats::pack(fee_stats, &mut fee_stats_account.data.borrow_mut())?;
+
+    Ok(())
+}
+
+/// Load the program-wide Config PDA, rejecting any account that isn't the
+/// expected PDA so fee processors can't be pointed at attacker-owned data.
+fn load_config(program_id: &Pubkey, config_account: &AccountInfo) -> Result<Config, ProgramError> {
+    let (expected_config, _bump) = Pubkey::find_program_address(&[CONFIG_SEED], program_id);
+
+    if config_account.key != &expected_config {
+        msg!("❌ Invalid config account");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Config::unpack(&config_account.data.borrow())
+}
+
+fn initialize_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    fee_recipient: Pubkey,
+    withdrawal_fee_bps: u64,
+    trading_fee_bps: u64,
+    escrow_enabled: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let admin_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_sysvar = next_account_info(account_info_iter)?;
+
+    if !admin_account.is_signer {
+        msg!("❌ Admin must sign config initialization");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if withdrawal_fee_bps > FEE_BPS_PRECISION || trading_fee_bps > FEE_BPS_PRECISION {
+        msg!("❌ Fee basis points cannot exceed {}", FEE_BPS_PRECISION);
+        return Err(AlphaBotError::FeeBpsTooHigh.into());
+    }
+
+    let (expected_config, bump) = Pubkey::find_program_address(&[CONFIG_SEED], program_id);
+
+    if config_account.key != &expected_config {
+        msg!("❌ Config account is not the program's Config PDA");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let rent = &Rent::from_account_info(rent_sysvar)?;
+    let space = Config::LEN;
+    let lamports = rent.minimum_balance(space);
+
+    let create_account_instruction = system_instruction::create_account(
+        admin_account.key,
+        config_account.key,
+        lamports,
+        space as u64,
+        program_id,
+    );
+
+    invoke_signed(
+        &create_account_instruction,
+        &[
+            admin_account.clone(),
+            config_account.clone(),
             system_program.clone(),
         ],
+        &[&[CONFIG_SEED, &[bump]]],
     )?;
 
-    msg!("✅ Trading fee processed: {} lamports → {}", fee_amount, FEE_RECIPIENT);
-    
-    // Emit event for tracking
-    msg!("EVENT:TRADING_FEE|{}|{}|{}", user_account.key, fee_amount, chrono::Utc::now().timestamp());
+    let config = Config {
+        is_initialized: true,
+        admin: *admin_account.key,
+        fee_recipient,
+        withdrawal_fee_bps,
+        trading_fee_bps,
+        escrow_enabled,
+    };
+
+    Config::pack(config, &mut config_account.data.borrow_mut())?;
+
+    msg!("✅ Config initialized. Admin: {}", admin_account.key);
+
+    Ok(())
+}
+
+fn update_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    fee_recipient: Pubkey,
+    withdrawal_fee_bps: u64,
+    trading_fee_bps: u64,
+    escrow_enabled: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let admin_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    if !admin_account.is_signer {
+        msg!("❌ Admin must sign config updates");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut config = load_config(program_id, config_account)?;
+
+    if config.admin != *admin_account.key {
+        msg!("❌ Only the current admin can update config");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if withdrawal_fee_bps > FEE_BPS_PRECISION || trading_fee_bps > FEE_BPS_PRECISION {
+        msg!("❌ Fee basis points cannot exceed {}", FEE_BPS_PRECISION);
+        return Err(AlphaBotError::FeeBpsTooHigh.into());
+    }
+
+    config.fee_recipient = fee_recipient;
+    config.withdrawal_fee_bps = withdrawal_fee_bps;
+    config.trading_fee_bps = trading_fee_bps;
+    config.escrow_enabled = escrow_enabled;
+
+    Config::pack(config, &mut config_account.data.borrow_mut())?;
+
+    msg!("✅ Config updated by admin: {}", admin_account.key);
 
     Ok(())
 }
@@ -253,6 +978,18 @@ fn initialize_fee_stats(
 
     let rent = &Rent::from_account_info(rent_sysvar)?;
 
+    // The fee stats account must be the PDA derived from this user, so a
+    // caller can't point fee processors at an account they fully control.
+    let (expected_fee_stats, bump) = Pubkey::find_program_address(
+        &[FEE_STATS_SEED, user_account.key.as_ref()],
+        program_id,
+    );
+
+    if fee_stats_account.key != &expected_fee_stats {
+        msg!("❌ Fee stats account is not the PDA for this user");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
     // Create fee stats account
     let space = FeeStats::LEN;
     let lamports = rent.minimum_balance(space);
@@ -265,13 +1002,14 @@ fn initialize_fee_stats(
         program_id,
     );
 
-    invoke(
+    invoke_signed(
         &create_account_instruction,
         &[
             user_account.clone(),
             fee_stats_account.clone(),
             system_program.clone(),
         ],
+        &[&[FEE_STATS_SEED, user_account.key.as_ref(), &[bump]]],
     )?;
 
     // Initialize fee stats data
@@ -281,7 +1019,7 @@ fn initialize_fee_stats(
         total_withdrawal_fees: 0,
         total_trading_fees: 0,
         fee_count: 0,
-        last_fee_timestamp: chrono::Utc::now().timestamp(),
+        last_fee_timestamp: Clock::get()?.unix_timestamp,
     };
 
     FeeStats::pack(fee_stats, &mut fee_stats_account.data.borrow_mut())?;
@@ -289,4 +1027,349 @@ fn initialize_fee_stats(
     msg!("✅ Fee stats account initialized for wallet: {}", user_account.key);
 
     Ok(())
+}
+
+fn create_fee_stream(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    start_ts: i64,
+    end_ts: i64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let admin_account = next_account_info(account_info_iter)?;
+    let stream_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_sysvar = next_account_info(account_info_iter)?;
+
+    if !admin_account.is_signer {
+        msg!("❌ Admin must sign fee stream creation");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, config_account)?;
+
+    if config.admin != *admin_account.key {
+        msg!("❌ Only the current admin can create the fee stream");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if end_ts <= start_ts {
+        msg!("❌ Stream end_ts must be after start_ts");
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let (expected_stream, bump) = Pubkey::find_program_address(&[STREAM_SEED], program_id);
+
+    if stream_account.key != &expected_stream {
+        msg!("❌ Stream account is not the program's StreamConfig PDA");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let rent = &Rent::from_account_info(rent_sysvar)?;
+    let space = StreamConfig::LEN;
+    let lamports = rent.minimum_balance(space);
+
+    let create_account_instruction = system_instruction::create_account(
+        admin_account.key,
+        stream_account.key,
+        lamports,
+        space as u64,
+        program_id,
+    );
+
+    invoke_signed(
+        &create_account_instruction,
+        &[
+            admin_account.clone(),
+            stream_account.clone(),
+            system_program.clone(),
+        ],
+        &[&[STREAM_SEED, &[bump]]],
+    )?;
+
+    let stream = StreamConfig {
+        is_initialized: true,
+        start_ts,
+        end_ts,
+        deposited: 0,
+        withdrawn: 0,
+    };
+
+    StreamConfig::pack(stream, &mut stream_account.data.borrow_mut())?;
+
+    msg!("✅ Fee stream created: {} → {}", start_ts, end_ts);
+
+    Ok(())
+}
+
+fn deposit_to_stream(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let depositor_account = next_account_info(account_info_iter)?;
+    let stream_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    deposit_to_stream_checked(program_id, depositor_account, stream_account, system_program, amount)?;
+
+    msg!("✅ Deposited {} lamports into fee stream", amount);
+
+    Ok(())
+}
+
+/// Validate `stream_account` is the program's StreamConfig PDA, move
+/// `amount` lamports into it, and fold it into `deposited`. Shared by the
+/// `DepositToStream` instruction and the fee processors' escrow path.
+fn deposit_to_stream_checked<'a>(
+    program_id: &Pubkey,
+    depositor_account: &AccountInfo<'a>,
+    stream_account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    amount: u64,
+) -> ProgramResult {
+    let (expected_stream, _bump) = Pubkey::find_program_address(&[STREAM_SEED], program_id);
+
+    if stream_account.key != &expected_stream {
+        msg!("❌ Invalid fee stream account");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let transfer_instruction = system_instruction::transfer(
+        depositor_account.key,
+        stream_account.key,
+        amount,
+    );
+
+    invoke(
+        &transfer_instruction,
+        &[
+            depositor_account.clone(),
+            stream_account.clone(),
+            system_program.clone(),
+        ],
+    )?;
+
+    let mut stream = StreamConfig::unpack(&stream_account.data.borrow())?;
+    stream.deposited = stream
+        .deposited
+        .checked_add(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    StreamConfig::pack(stream, &mut stream_account.data.borrow_mut())?;
+
+    Ok(())
+}
+
+/// Linear-vesting arithmetic for the fee stream. Split out of
+/// `withdraw_vested` so it can be unit tested without constructing
+/// `AccountInfo` fixtures. Assumes `now >= start_ts`; callers should reject
+/// withdrawals before the stream starts up front.
+fn compute_withdrawable_vested(
+    start_ts: i64,
+    end_ts: i64,
+    deposited: u64,
+    withdrawn: u64,
+    now: i64,
+) -> Result<u64, ProgramError> {
+    let elapsed = now.saturating_sub(start_ts).max(0) as u64;
+    let duration = (end_ts - start_ts) as u64;
+
+    let vested = deposited
+        .checked_mul(elapsed.min(duration))
+        .and_then(|x| x.checked_div(duration))
+        .ok_or(ProgramError::ArithmeticOverflow)?
+        .min(deposited);
+
+    Ok(vested.saturating_sub(withdrawn))
+}
+
+fn withdraw_vested(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let recipient_account = next_account_info(account_info_iter)?;
+    let stream_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    if !recipient_account.is_signer {
+        msg!("❌ Fee recipient must sign the withdrawal");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, config_account)?;
+
+    if config.fee_recipient != *recipient_account.key {
+        msg!("❌ Only the configured fee recipient can withdraw from the stream");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let (expected_stream, _bump) = Pubkey::find_program_address(&[STREAM_SEED], program_id);
+
+    if stream_account.key != &expected_stream {
+        msg!("❌ Invalid fee stream account");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let mut stream = StreamConfig::unpack(&stream_account.data.borrow())?;
+
+    let now = Clock::get()?.unix_timestamp;
+
+    if now < stream.start_ts {
+        msg!("❌ Fee stream has not started vesting yet");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let withdrawable = compute_withdrawable_vested(
+        stream.start_ts,
+        stream.end_ts,
+        stream.deposited,
+        stream.withdrawn,
+        now,
+    )?;
+
+    if withdrawable == 0 {
+        msg!("⚠️ No vested balance available to withdraw");
+        return Ok(());
+    }
+
+    **stream_account.try_borrow_mut_lamports()? -= withdrawable;
+    **recipient_account.try_borrow_mut_lamports()? += withdrawable;
+
+    stream.withdrawn = stream
+        .withdrawn
+        .checked_add(withdrawable)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    StreamConfig::pack(stream, &mut stream_account.data.borrow_mut())?;
+
+    msg!("✅ Withdrew {} vested lamports to {}", withdrawable, recipient_account.key);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(kind: FeeEntryKind, amount: u64) -> FeeEntry {
+        FeeEntry { kind, amount }
+    }
+
+    #[test]
+    fn batch_fees_sum_per_kind_bps() {
+        let entries = vec![
+            entry(FeeEntryKind::Withdrawal, 1_000_000),
+            entry(FeeEntryKind::Trading, 2_000_000),
+        ];
+
+        // 1% withdrawal fee, 2% trading fee
+        let (total, per_entry) = compute_batch_fees(&entries, 100, 200).unwrap();
+
+        assert_eq!(per_entry, vec![10_000, 40_000]);
+        assert_eq!(total, 50_000);
+    }
+
+    #[test]
+    fn batch_fees_empty_batch_is_zero() {
+        let (total, per_entry) = compute_batch_fees(&[], 100, 200).unwrap();
+        assert_eq!(total, 0);
+        assert!(per_entry.is_empty());
+    }
+
+    #[test]
+    fn batch_fees_zero_bps_entries_are_zero() {
+        let entries = vec![entry(FeeEntryKind::Withdrawal, 1_000_000)];
+        let (total, per_entry) = compute_batch_fees(&entries, 0, 0).unwrap();
+        assert_eq!(total, 0);
+        assert_eq!(per_entry, vec![0]);
+    }
+
+    #[test]
+    fn batch_fees_rejects_overflowing_entry() {
+        let entries = vec![entry(FeeEntryKind::Withdrawal, u64::MAX)];
+        assert_eq!(
+            compute_batch_fees(&entries, FEE_BPS_PRECISION, 0),
+            Err(ProgramError::ArithmeticOverflow)
+        );
+    }
+
+    #[test]
+    fn batch_fees_rejects_overflowing_total() {
+        // Each entry's fee (u64::MAX / 10_000) fits on its own; 10_001 of them
+        // overflow the running total.
+        let entries = vec![entry(FeeEntryKind::Withdrawal, u64::MAX / FEE_BPS_PRECISION); 10_001];
+        assert_eq!(
+            compute_batch_fees(&entries, FEE_BPS_PRECISION, 0),
+            Err(ProgramError::ArithmeticOverflow)
+        );
+    }
+
+    #[test]
+    fn withdrawal_fees_ignore_trading_entries() {
+        let entries = vec![
+            entry(FeeEntryKind::Withdrawal, 1_000_000),
+            entry(FeeEntryKind::Trading, 2_000_000),
+        ];
+        let (_, per_entry_fees) = compute_batch_fees(&entries, 100, 200).unwrap();
+        assert_eq!(sum_withdrawal_fees(&entries, &per_entry_fees).unwrap(), 10_000);
+    }
+
+    #[test]
+    fn withdrawal_fees_zero_when_batch_is_all_trading() {
+        let entries = vec![entry(FeeEntryKind::Trading, 1_000_000)];
+        let (_, per_entry_fees) = compute_batch_fees(&entries, 100, 200).unwrap();
+        assert_eq!(sum_withdrawal_fees(&entries, &per_entry_fees).unwrap(), 0);
+    }
+
+    #[test]
+    fn withdrawal_fees_rejects_overflowing_total() {
+        let entries = vec![
+            entry(FeeEntryKind::Withdrawal, u64::MAX),
+            entry(FeeEntryKind::Withdrawal, u64::MAX),
+        ];
+        let per_entry_fees = vec![u64::MAX, u64::MAX];
+        assert_eq!(
+            sum_withdrawal_fees(&entries, &per_entry_fees),
+            Err(ProgramError::ArithmeticOverflow)
+        );
+    }
+
+    #[test]
+    fn vesting_at_start_is_fully_unvested() {
+        let withdrawable = compute_withdrawable_vested(1_000, 2_000, 10_000, 0, 1_000).unwrap();
+        assert_eq!(withdrawable, 0);
+    }
+
+    #[test]
+    fn vesting_midway_is_proportional() {
+        let withdrawable = compute_withdrawable_vested(1_000, 2_000, 10_000, 0, 1_500).unwrap();
+        assert_eq!(withdrawable, 5_000);
+    }
+
+    #[test]
+    fn vesting_at_end_is_fully_vested() {
+        let withdrawable = compute_withdrawable_vested(1_000, 2_000, 10_000, 0, 2_000).unwrap();
+        assert_eq!(withdrawable, 10_000);
+    }
+
+    #[test]
+    fn vesting_after_end_is_capped_at_deposited() {
+        let withdrawable = compute_withdrawable_vested(1_000, 2_000, 10_000, 0, 5_000).unwrap();
+        assert_eq!(withdrawable, 10_000);
+    }
+
+    #[test]
+    fn vesting_subtracts_prior_withdrawals() {
+        let withdrawable = compute_withdrawable_vested(1_000, 2_000, 10_000, 4_000, 2_000).unwrap();
+        assert_eq!(withdrawable, 6_000);
+    }
+
+    #[test]
+    fn vesting_fully_withdrawn_is_zero() {
+        let withdrawable = compute_withdrawable_vested(1_000, 2_000, 10_000, 10_000, 2_000).unwrap();
+        assert_eq!(withdrawable, 0);
+    }
 }
\ No newline at end of file